@@ -0,0 +1,75 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, BinOp, Expr, ExprAssignOp, ExprBinary, ExprParen, ExprUnary, UnOp};
+
+/// Rewrites an arithmetic expression into its checked-arithmetic form, returning a
+/// `MarginfiError::MathError` (via `math_error!()`) on overflow, so that callers can keep
+/// writing `cm!(a + b * c)` instead of a chain of
+/// `a.checked_add(b.checked_mul(c).ok_or_else(math_error!())?).ok_or_else(math_error!())?`.
+///
+/// `cm!(a += b)` (and `-=`, `*=`, `/=`) expands to `a = cm!(a + b)`. Grouping, unary negation and
+/// mixed operators are supported; precedence and evaluation order follow the input expression as
+/// parsed by `syn`, so `cm!(a + b * c)` multiplies before adding, just like plain Rust.
+#[proc_macro]
+pub fn cm(input: TokenStream) -> TokenStream {
+    let expr = parse_macro_input!(input as Expr);
+    TokenStream::from(expand(&expr))
+}
+
+fn expand(expr: &Expr) -> TokenStream2 {
+    match expr {
+        Expr::Binary(ExprBinary { left, op, right, .. }) => {
+            let left = expand(left);
+            let right = expand(right);
+            checked_op(&left, op, &right)
+        }
+        Expr::Paren(ExprParen { expr, .. }) => {
+            let inner = expand(expr);
+            quote! { (#inner) }
+        }
+        Expr::Unary(ExprUnary { op: UnOp::Neg(_), expr, .. }) => {
+            let inner = expand(expr);
+            quote! { (#inner).checked_neg().ok_or_else(crate::math_error!())? }
+        }
+        Expr::AssignOp(ExprAssignOp { left, op, right, .. }) => {
+            let right = expand(right);
+            let value = checked_op_from_assign(&quote! { #left }, op, &right);
+            quote! { #left = #value }
+        }
+        // Leaves (identifiers, literals, field/method access, ...) pass through unchanged.
+        other => quote! { #other },
+    }
+}
+
+fn checked_op(left: &TokenStream2, op: &BinOp, right: &TokenStream2) -> TokenStream2 {
+    let method = checked_method(op);
+    quote! { (#left).#method(#right).ok_or_else(crate::math_error!())? }
+}
+
+fn checked_op_from_assign(left: &TokenStream2, op: &syn::BinOp, right: &TokenStream2) -> TokenStream2 {
+    let method = checked_assign_method(op);
+    quote! { (#left).#method(#right).ok_or_else(crate::math_error!())? }
+}
+
+fn checked_method(op: &BinOp) -> syn::Ident {
+    let name = match op {
+        BinOp::Add(_) => "checked_add",
+        BinOp::Sub(_) => "checked_sub",
+        BinOp::Mul(_) => "checked_mul",
+        BinOp::Div(_) => "checked_div",
+        _ => panic!("cm! only supports +, -, *, / and their compound-assignment forms"),
+    };
+    syn::Ident::new(name, proc_macro2::Span::call_site())
+}
+
+fn checked_assign_method(op: &BinOp) -> syn::Ident {
+    let name = match op {
+        BinOp::AddEq(_) => "checked_add",
+        BinOp::SubEq(_) => "checked_sub",
+        BinOp::MulEq(_) => "checked_mul",
+        BinOp::DivEq(_) => "checked_div",
+        _ => panic!("cm! only supports +=, -=, *=, /="),
+    };
+    syn::Ident::new(name, proc_macro2::Span::call_site())
+}
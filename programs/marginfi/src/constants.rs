@@ -0,0 +1,9 @@
+use fixed::types::I80F48;
+use fixed_macro::types::I80F48;
+
+/// Number of seconds in a 365-day year, used to annualize/de-annualize interest rates.
+pub const SECONDS_PER_YEAR: i64 = 31_536_000;
+
+/// Slack left in an account's post-liquidation maintenance health so a liquidation can't be
+/// sized to flip the account far into positive health.
+pub const POST_LIQUIDATION_HEALTH_BUFFER: I80F48 = I80F48!(0.01);
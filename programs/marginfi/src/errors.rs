@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum MarginfiError {
+    #[msg("Math error")]
+    MathError,
+    #[msg("Bank not found")]
+    BankNotFound,
+    #[msg("Lending account balance not found")]
+    LendingAccountBalanceNotFound,
+    #[msg("Lending account balance slots are full")]
+    LendingAccountBalanceSlotsFull,
+    #[msg("Borrowing is not allowed")]
+    BorrowingNotAllowed,
+    #[msg("Bad account health")]
+    BadAccountHealth,
+    #[msg("Oracle price is stale")]
+    StaleOracle,
+    #[msg("Oracle confidence interval is too wide")]
+    UnreliableOracle,
+    #[msg("Account is not eligible for liquidation")]
+    HealthyAccount,
+    #[msg("Liquidation is larger than the account's unhealthy balance allows")]
+    LiquidationTooLarge,
+    #[msg("Withdrawal is larger than the bank's collected fees")]
+    InsufficientFees,
+}
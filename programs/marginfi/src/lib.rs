@@ -0,0 +1,15 @@
+pub mod constants;
+pub mod errors;
+#[macro_use]
+pub mod macros;
+pub mod prelude;
+pub mod state;
+
+use anchor_lang::prelude::*;
+
+declare_id!("MFv2hWf31Z9kbCa1snEPYctwafyhdvnV7FZnsebVacA");
+
+#[program]
+pub mod marginfi {
+    use super::*;
+}
@@ -0,0 +1,22 @@
+/// Builds a closure yielding a `MarginfiError::MathError`, annotated with the call site, for use
+/// with `Option::ok_or_else`/`Result::map_err` on checked arithmetic.
+#[macro_export]
+macro_rules! math_error {
+    () => {{
+        || {
+            let error_code = $crate::prelude::MarginfiError::MathError;
+            anchor_lang::prelude::msg!("Error \"{}\" thrown at {}:{}", error_code, file!(), line!());
+            error_code
+        }
+    }};
+}
+
+/// Returns early with `$err` unless `$cond` holds.
+#[macro_export]
+macro_rules! check {
+    ($cond:expr, $err:expr) => {
+        if !($cond) {
+            return Err(anchor_lang::prelude::error!($err));
+        }
+    };
+}
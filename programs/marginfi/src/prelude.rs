@@ -0,0 +1,3 @@
+pub use crate::errors::MarginfiError;
+
+pub type MarginfiResult<G = ()> = Result<G>;
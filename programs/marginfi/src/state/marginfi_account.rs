@@ -1,12 +1,15 @@
 use super::marginfi_group::{Bank, LendingPool, MarginfiGroup, WrappedI80F48};
 use crate::{
-    check, math_error,
+    check,
+    constants::POST_LIQUIDATION_HEALTH_BUFFER,
+    math_error,
     prelude::{MarginfiError, MarginfiResult},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token::{transfer, Transfer};
 use fixed::types::I80F48;
 use fixed_macro::types::I80F48;
+use marginfi_macros::cm;
 use pyth_sdk_solana::{Price, PriceFeed};
 use std::{
     cmp::{max, min},
@@ -48,25 +51,61 @@ const EXP_10_I80F48: [I80F48; 15] = [
 
 const EXPONENT: i32 = 6;
 
-/// Convert a price `price.price` with decimal exponent `price.expo` to an I80F48 representation with exponent 6.
-pub fn pyth_price_to_i80f48(price: &Price) -> MarginfiResult<I80F48> {
+/// Number of standard deviations (Pyth `conf`) the price band extends on either side of the
+/// point price, per Pyth's own guidance for deriving a safe trading range from `conf`.
+const CONFIDENCE_INTERVAL_MULTIPLE: I80F48 = I80F48!(2.12);
+
+/// Which side of the confidence band to value an exposure at. Biasing the side that hurts the
+/// account (assets down, liabilities up) makes a wide confidence interval always reduce measured
+/// health, instead of leaving it to chance which side of the interval the point price lands on.
+#[derive(Clone, Copy)]
+pub enum PriceBias {
+    Low,
+    High,
+}
+
+/// Convert `price.price` (with decimal exponent `price.expo`) to an I80F48 representation with
+/// exponent 6, optionally biased to the low or high end of the `conf`-derived confidence band.
+pub fn pyth_price_to_i80f48(price: &Price, bias: Option<PriceBias>) -> MarginfiResult<I80F48> {
     let pyth_price = price.price;
     let pyth_expo = price.expo;
 
     let expo_delta = EXPONENT - pyth_expo;
     let expo_scale = EXP_10_I80F48[expo_delta.unsigned_abs() as usize];
 
-    let price = I80F48::from_num(pyth_price);
+    let scale = |value: I80F48| -> MarginfiResult<I80F48> {
+        Ok(if expo_delta < 0 {
+            value.checked_div(expo_scale).ok_or_else(math_error!())?
+        } else {
+            value.checked_mul(expo_scale).ok_or_else(math_error!())?
+        })
+    };
 
-    let price = if expo_delta < 0 {
-        price.checked_div(expo_scale).ok_or_else(math_error!())?
-    } else {
-        price.checked_mul(expo_scale).ok_or_else(math_error!())?
+    let scaled_price = scale(I80F48::from_num(pyth_price))?;
+
+    let scaled_price = match bias {
+        None => scaled_price,
+        Some(bias) => {
+            let conf = scale(I80F48::from_num(price.conf))?;
+            let conf_offset = conf
+                .checked_mul(CONFIDENCE_INTERVAL_MULTIPLE)
+                .ok_or_else(math_error!())?;
+
+            match bias {
+                PriceBias::Low => scaled_price
+                    .checked_sub(conf_offset)
+                    .ok_or_else(math_error!())?,
+                PriceBias::High => scaled_price
+                    .checked_add(conf_offset)
+                    .ok_or_else(math_error!())?,
+            }
+        }
     };
 
-    Ok(price)
+    Ok(scaled_price)
 }
 
+#[derive(Clone, Copy)]
 pub enum WeightType {
     Initial,
     Maintenance,
@@ -111,23 +150,50 @@ impl<'a> BankAccountWithPriceFeed<'a> {
 
     pub fn calc_weighted_assets_and_liabilities_values(
         &self,
-        weight_type: WeightType,
+        requirement_type: RiskRequirementType,
     ) -> MarginfiResult<(I80F48, I80F48)> {
-        // TODO: Expire price, and check confidence interval
-        let price = self.price_feed.get_price_unchecked();
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let price = self.bank.get_checked_price(&self.price_feed, current_timestamp)?;
 
         let deposits_qt = self
             .bank
             .get_deposit_amount(self.balance.deposit_shares.into())?;
         let liabilities_qt = self
             .bank
-            .get_deposit_amount(self.balance.liability_shares.into())?;
-        let (deposit_weight, liability_weight) = self.bank.config.get_weights(weight_type); // TODO: asset-specific weights
+            .get_liability_amount(self.balance.liability_shares.into())?;
+        let (deposit_weight, liability_weight) =
+            self.bank.config.get_weights(requirement_type.to_weight_type()); // TODO: asset-specific weights
+
+        // Assets are valued at the low end of the confidence band, liabilities at the high end,
+        // so a wide spread can only hurt, never help, measured health.
+        let mut asset_value =
+            calc_asset_value(deposits_qt, &price, Some(deposit_weight), Some(PriceBias::Low))?;
+        let mut liability_value = calc_asset_value(
+            liabilities_qt,
+            &price,
+            Some(liability_weight),
+            Some(PriceBias::High),
+        )?;
 
-        Ok((
-            calc_asset_value(deposits_qt, &price, Some(deposit_weight))?,
-            calc_asset_value(liabilities_qt, &price, Some(liability_weight))?,
-        ))
+        // Borrow-time checks additionally dampen against a slow-moving stable price so a
+        // short-lived oracle spike can't be used to open an outsized position. Maintenance
+        // (liquidation) checks react to the live oracle price directly.
+        if let RiskRequirementType::Initial = requirement_type {
+            let stable_price: I80F48 = self.bank.stable_price_model.stable_price.into();
+            let stable_asset_value =
+                calc_value_at_scaled_price(deposits_qt, stable_price, Some(deposit_weight), price.expo)?;
+            let stable_liability_value = calc_value_at_scaled_price(
+                liabilities_qt,
+                stable_price,
+                Some(liability_weight),
+                price.expo,
+            )?;
+
+            asset_value = min(asset_value, stable_asset_value);
+            liability_value = max(liability_value, stable_liability_value);
+        }
+
+        Ok((asset_value, liability_value))
     }
 }
 
@@ -144,39 +210,45 @@ pub fn calc_asset_value(
     asset_quantity: I80F48,
     pyth_price: &Price,
     weight: Option<I80F48>,
+    bias: Option<PriceBias>,
 ) -> MarginfiResult<I80F48> {
-    let price = pyth_price_to_i80f48(pyth_price)?;
-    let scaling_factor = EXP_10_I80F48[pyth_price.expo.unsigned_abs() as usize];
+    let price = pyth_price_to_i80f48(pyth_price, bias)?;
+    calc_value_at_scaled_price(asset_quantity, price, weight, pyth_price.expo)
+}
+
+/// Shared by [`calc_asset_value`] and the stable-price blend: value a quantity at a price
+/// already expressed in the exponent-6 convention produced by `pyth_price_to_i80f48`.
+#[inline]
+fn calc_value_at_scaled_price(
+    asset_quantity: I80F48,
+    scaled_price: I80F48,
+    weight: Option<I80F48>,
+    pyth_expo: i32,
+) -> MarginfiResult<I80F48> {
+    let scaling_factor = EXP_10_I80F48[pyth_expo.unsigned_abs() as usize];
 
     let weighted_asset_qt = if let Some(weight) = weight {
-        asset_quantity.checked_mul(weight).unwrap()
+        cm!(asset_quantity * weight)
     } else {
         asset_quantity
     };
 
-    let asset_value = weighted_asset_qt
-        .checked_mul(price)
-        .ok_or_else(math_error!())?
-        .checked_div(scaling_factor)
-        .ok_or_else(math_error!())?;
+    let asset_value = cm!(weighted_asset_qt * scaled_price / scaling_factor);
 
     Ok(asset_value)
 }
 
 #[inline]
 pub fn calc_asset_quantity(asset_value: I80F48, pyth_price: &Price) -> MarginfiResult<I80F48> {
-    let price = pyth_price_to_i80f48(pyth_price)?;
+    let price = pyth_price_to_i80f48(pyth_price, None)?;
     let scaling_factor = EXP_10_I80F48[pyth_price.expo.unsigned_abs() as usize];
 
-    let asset_qt = asset_value
-        .checked_mul(scaling_factor)
-        .ok_or_else(math_error!())?
-        .checked_div(price)
-        .ok_or_else(math_error!())?;
+    let asset_qt = cm!(asset_value * scaling_factor / price);
 
     Ok(asset_qt)
 }
 
+#[derive(Clone, Copy)]
 pub enum RiskRequirementType {
     Initial,
     Maintenance,
@@ -212,29 +284,23 @@ impl<'a> RiskEngine<'a> {
         })
     }
 
-    pub fn check_account_health(&self, requirement_type: RiskRequirementType) -> MarginfiResult {
+    /// Weighted assets minus weighted liabilities. Positive means healthy.
+    pub fn get_account_health(&self, requirement_type: RiskRequirementType) -> MarginfiResult<I80F48> {
         let (total_weighted_assets, total_weighted_liabilities) = self
             .bank_accounts_with_price
             .iter()
-            .map(|a| {
-                a.calc_weighted_assets_and_liabilities_values(requirement_type.to_weight_type())
-            })
+            .map(|a| a.calc_weighted_assets_and_liabilities_values(requirement_type))
             .try_fold((I80F48::ZERO, I80F48::ZERO), |(ta, tl), res| {
                 let (assets, liabilities) = res?;
-                let total_assets_sum = ta.checked_add(assets).ok_or_else(math_error!())?;
-                let total_liabilities_sum =
-                    tl.checked_add(liabilities).ok_or_else(math_error!())?;
-
-                Ok::<_, ProgramError>((total_assets_sum, total_liabilities_sum))
+                Ok::<_, ProgramError>((cm!(ta + assets), cm!(tl + liabilities)))
             })?;
 
-        println!(
-            "assets {} - liabs: {}",
-            total_weighted_assets, total_weighted_liabilities
-        );
+        Ok(cm!(total_weighted_assets - total_weighted_liabilities))
+    }
 
+    pub fn check_account_health(&self, requirement_type: RiskRequirementType) -> MarginfiResult {
         check!(
-            total_weighted_assets > total_weighted_liabilities,
+            self.get_account_health(requirement_type)? > I80F48::ZERO,
             MarginfiError::BadAccountHealth
         );
 
@@ -274,6 +340,73 @@ impl LendingAccount {
     pub fn get_active_balances_iter(&self) -> impl Iterator<Item = &Balance> {
         self.balances.iter().filter_map(|b| b.as_ref())
     }
+
+    /// Find the balance for `bank_index`, creating an empty one in the first free slot if the
+    /// account doesn't hold one yet.
+    pub fn get_or_create_balance_mut(&mut self, bank_index: u16) -> MarginfiResult<&mut Balance> {
+        let balance_index = self
+            .get_active_balances_iter()
+            .position(|b| b.bank_index as usize == bank_index as usize);
+
+        let index = match balance_index {
+            Some(index) => index,
+            None => {
+                let empty_index = self
+                    .get_first_empty_balance()
+                    .ok_or_else(|| error!(MarginfiError::LendingAccountBalanceSlotsFull))?;
+
+                self.balances[empty_index] = Some(Balance {
+                    bank_index: bank_index as u8,
+                    deposit_shares: I80F48::ZERO.into(),
+                    liability_shares: I80F48::ZERO.into(),
+                });
+
+                empty_index
+            }
+        };
+
+        Ok(self.balances[index].as_mut().unwrap())
+    }
+}
+
+/// Credit `amount` to `balance`: first against any outstanding liability, with the remainder
+/// booked as a new deposit. Returns the portion of `amount` that actually paid down the
+/// liability — callers that size something off the real repayment (e.g. liquidation's seize
+/// amount) must use the return value, not the raw `amount`.
+fn apply_deposit(balance: &mut Balance, bank: &mut Bank, amount: I80F48) -> MarginfiResult<I80F48> {
+    let liability_shares: I80F48 = balance.liability_shares.into();
+    let liability_value = bank.get_liability_amount(liability_shares)?;
+
+    let (deposit_value_delta, liability_replay_value_delta) = (
+        max(cm!(amount - liability_value), I80F48::ZERO),
+        min(liability_value, amount),
+    );
+
+    let deposit_shares_delta = bank.get_deposit_shares(deposit_value_delta)?;
+    balance.change_deposit_shares(deposit_shares_delta)?;
+    bank.change_deposit_shares(deposit_shares_delta)?;
+
+    let liability_shares_delta = bank.get_liability_shares(liability_replay_value_delta)?;
+    balance.change_liability_shares(-liability_shares_delta)?;
+    bank.change_liability_shares(-liability_shares_delta)?;
+
+    Ok(liability_replay_value_delta)
+}
+
+/// Move `shares` worth of deposit shares from one lending account's balance to another's, within
+/// the same bank. The bank's totals are unaffected since the shares are only changing owner.
+fn transfer_deposit_shares(
+    from: &mut LendingAccount,
+    to: &mut LendingAccount,
+    bank_index: u16,
+    shares: I80F48,
+) -> MarginfiResult {
+    from.get_or_create_balance_mut(bank_index)?
+        .change_deposit_shares(-shares)?;
+    to.get_or_create_balance_mut(bank_index)?
+        .change_deposit_shares(shares)?;
+
+    Ok(())
 }
 
 #[zero_copy]
@@ -285,20 +418,16 @@ pub struct Balance {
 
 impl Balance {
     pub fn change_deposit_shares(&mut self, delta: I80F48) -> MarginfiResult {
-        let deposit_shares: I80F48 = self.deposit_shares.into();
-        self.deposit_shares = deposit_shares
-            .checked_add(delta)
-            .ok_or_else(math_error!())?
-            .into();
+        let mut deposit_shares: I80F48 = self.deposit_shares.into();
+        cm!(deposit_shares += delta);
+        self.deposit_shares = deposit_shares.into();
         Ok(())
     }
 
     pub fn change_liability_shares(&mut self, delta: I80F48) -> MarginfiResult {
-        let liability_shares: I80F48 = self.liability_shares.into();
-        self.liability_shares = liability_shares
-            .checked_add(delta)
-            .ok_or_else(math_error!())?
-            .into();
+        let mut liability_shares: I80F48 = self.liability_shares.into();
+        cm!(liability_shares += delta);
+        self.liability_shares = liability_shares.into();
         Ok(())
     }
 }
@@ -399,47 +528,36 @@ impl<'a> BankAccountWrapper<'a> {
         Ok(Self { balance, bank })
     }
 
-    pub fn account_deposit(&mut self, amount: I80F48) -> MarginfiResult {
-        let balance = &mut self.balance;
-        let bank = &mut self.bank;
-
-        let liability_shares: I80F48 = balance.liability_shares.into();
-
-        let liability_value = bank.get_liability_amount(liability_shares)?;
-
-        let (deposit_value_delta, liability_replay_value_delta) = (
-            max(
-                amount
-                    .checked_sub(liability_value)
-                    .ok_or_else(math_error!())?,
-                I80F48::ZERO,
-            ),
-            min(liability_value, amount),
-        );
-
-        let deposit_shares_delta = bank.get_deposit_shares(deposit_value_delta)?;
-        balance.change_deposit_shares(deposit_shares_delta)?;
-        bank.change_deposit_shares(deposit_shares_delta)?;
+    /// Returns the portion of `amount` that actually paid down an existing liability (the rest,
+    /// if any, becomes a new deposit) — callers that size something off the real repayment (e.g.
+    /// liquidation's seize amount) must use this, not the raw `amount`.
+    pub fn account_deposit(&mut self, amount: I80F48, price_feed: &PriceFeed) -> MarginfiResult<I80F48> {
+        self.bank.accrue_interest()?;
+        self.bank.update_stable_price_from_feed(price_feed)?;
 
-        let liability_shares_delta = bank.get_liability_shares(liability_replay_value_delta)?;
-        balance.change_liability_shares(-liability_shares_delta)?;
-        bank.change_liability_shares(-liability_shares_delta)?;
-
-        Ok(())
+        apply_deposit(self.balance, self.bank, amount)
     }
 
     /// Borrow an asset, will withdraw existing deposits if they exist.
-    pub fn account_borrow(&mut self, amount: I80F48) -> MarginfiResult {
-        self.account_credit_asset(amount, true)
+    pub fn account_borrow(&mut self, amount: I80F48, price_feed: &PriceFeed) -> MarginfiResult {
+        self.account_credit_asset(amount, true, price_feed)
     }
 
     /// Withdraw a deposit, will error if there is not enough deposit.
     /// Borrowing is not allowed.
-    pub fn account_withdraw(&mut self, amount: I80F48) -> MarginfiResult {
-        self.account_credit_asset(amount, false)
+    pub fn account_withdraw(&mut self, amount: I80F48, price_feed: &PriceFeed) -> MarginfiResult {
+        self.account_credit_asset(amount, false, price_feed)
     }
 
-    fn account_credit_asset(&mut self, amount: I80F48, allow_borrow: bool) -> MarginfiResult {
+    fn account_credit_asset(
+        &mut self,
+        amount: I80F48,
+        allow_borrow: bool,
+        price_feed: &PriceFeed,
+    ) -> MarginfiResult {
+        self.bank.accrue_interest()?;
+        self.bank.update_stable_price_from_feed(price_feed)?;
+
         let balance = &mut self.balance;
         let bank = &mut self.bank;
 
@@ -449,12 +567,7 @@ impl<'a> BankAccountWrapper<'a> {
 
         let (deposit_remove_value_delta, liability_value_delta) = (
             min(deposit_value, amount),
-            max(
-                amount
-                    .checked_sub(deposit_value)
-                    .ok_or_else(math_error!())?,
-                I80F48::ZERO,
-            ),
+            max(cm!(amount - deposit_value), I80F48::ZERO),
         );
 
         check!(
@@ -470,6 +583,13 @@ impl<'a> BankAccountWrapper<'a> {
         balance.change_liability_shares(liability_shares_delta)?;
         bank.change_liability_shares(liability_shares_delta)?;
 
+        if liability_value_delta > I80F48::ZERO {
+            // New borrows carry a one-time origination fee, minted as extra liability shares
+            // against the borrower and credited straight to the bank's collected fees.
+            let fee_shares_delta = bank.apply_loan_origination_fee(liability_value_delta)?;
+            balance.change_liability_shares(fee_shares_delta)?;
+        }
+
         Ok(())
     }
 
@@ -493,3 +613,190 @@ impl<'a> BankAccountWrapper<'a> {
             .withdraw_spl_transfer(amount, accounts, program, signer_seeds)
     }
 }
+
+/// Value seized from collateral for repaying `liability_repaid_value` of debt, inclusive of the
+/// collateral bank's liquidation bonus. Zero when nothing was actually repaid, so a liquidatee
+/// with no debt in `liability_bank_index` has nothing seized from them.
+fn calc_seized_value(liability_repaid_value: I80F48, liquidation_bonus: I80F48) -> MarginfiResult<I80F48> {
+    let bonus_multiplier = cm!(I80F48::from_num(1) + liquidation_bonus);
+
+    Ok(cm!(liability_repaid_value * bonus_multiplier))
+}
+
+/// Repay part of an unhealthy account's liability in `liability_bank_index` and seize collateral
+/// from `collateral_bank_index` at that bank's configured liquidation bonus.
+pub fn liquidate<'info>(
+    margin_group: &MarginfiGroup,
+    liquidatee_marginfi_account: &mut MarginfiAccount,
+    liquidator_lending_account: &mut LendingAccount,
+    lending_pool: &mut LendingPool,
+    liability_bank_index: u16,
+    collateral_bank_index: u16,
+    repay_amount: I80F48,
+    oracle_ais: &[AccountInfo<'info>],
+) -> MarginfiResult {
+    {
+        let engine = RiskEngine::new(margin_group, liquidatee_marginfi_account, oracle_ais)?;
+        check!(
+            engine.get_account_health(RiskRequirementType::Maintenance)? < I80F48::ZERO,
+            MarginfiError::HealthyAccount
+        );
+    }
+
+    let pyth_accounts = create_pyth_account_map(oracle_ais)?;
+    let liability_price_feed = lending_pool.banks[liability_bank_index as usize]
+        .as_ref()
+        .ok_or_else(|| error!(MarginfiError::BankNotFound))?
+        .load_price_feed(&pyth_accounts)?;
+
+    let liability_repaid_value = BankAccountWrapper::find_or_create(
+        liability_bank_index,
+        lending_pool,
+        &mut liquidatee_marginfi_account.lending_account,
+    )?
+    .account_deposit(repay_amount, &liability_price_feed)?;
+
+    let liquidation_bonus: I80F48 = lending_pool.banks[collateral_bank_index as usize]
+        .as_ref()
+        .ok_or_else(|| error!(MarginfiError::BankNotFound))?
+        .config
+        .liquidation_bonus
+        .into();
+    let seized_value = calc_seized_value(liability_repaid_value, liquidation_bonus)?;
+
+    let collateral_bank = lending_pool.banks[collateral_bank_index as usize]
+        .as_mut()
+        .ok_or_else(|| error!(MarginfiError::BankNotFound))?;
+    collateral_bank.accrue_interest()?;
+    let collateral_price_feed = collateral_bank.load_price_feed(&pyth_accounts)?;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let price = collateral_bank.get_checked_price(&collateral_price_feed, current_timestamp)?;
+    let seized_quantity = calc_asset_quantity(seized_value, &price)?;
+    let seized_shares = collateral_bank.get_deposit_shares(seized_quantity)?;
+
+    let liquidatee_collateral_shares = liquidatee_marginfi_account
+        .lending_account
+        .get_active_balances_iter()
+        .find(|balance| balance.bank_index as usize == collateral_bank_index as usize)
+        .map_or(I80F48::ZERO, |balance| balance.deposit_shares.into());
+    check!(
+        seized_shares <= liquidatee_collateral_shares,
+        MarginfiError::LiquidationTooLarge
+    );
+
+    transfer_deposit_shares(
+        &mut liquidatee_marginfi_account.lending_account,
+        liquidator_lending_account,
+        collateral_bank_index,
+        seized_shares,
+    )?;
+
+    let engine = RiskEngine::new(margin_group, liquidatee_marginfi_account, oracle_ais)?;
+    check!(
+        engine.get_account_health(RiskRequirementType::Maintenance)?
+            <= POST_LIQUIDATION_HEALTH_BUFFER,
+        MarginfiError::LiquidationTooLarge
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::marginfi_group::{BankConfig, StablePriceModel};
+
+    fn test_bank() -> Bank {
+        Bank {
+            mint_pk: Pubkey::default(),
+            mint_decimals: 0,
+            vault: Pubkey::default(),
+            vault_authority_bump: 0,
+            total_deposit_shares: I80F48::ZERO.into(),
+            total_liability_shares: I80F48::from_num(10).into(),
+            deposit_index: I80F48::from_num(1).into(),
+            borrow_index: I80F48::from_num(1).into(),
+            last_update_ts: 0,
+            util0: I80F48::from_num(0.5).into(),
+            rate0: I80F48::from_num(0.1).into(),
+            util1: I80F48::from_num(0.8).into(),
+            rate1: I80F48::from_num(0.3).into(),
+            max_rate: I80F48::from_num(1).into(),
+            stable_price_model: StablePriceModel::default(),
+            loan_origination_fee_rate: I80F48::ZERO.into(),
+            loan_fee_rate: I80F48::ZERO.into(),
+            collected_fees: I80F48::ZERO.into(),
+            config: BankConfig::default(),
+        }
+    }
+
+    fn test_balance(liability_shares: I80F48) -> Balance {
+        Balance {
+            bank_index: 0,
+            deposit_shares: I80F48::ZERO.into(),
+            liability_shares: liability_shares.into(),
+        }
+    }
+
+    #[test]
+    fn repaying_more_than_owed_only_repays_what_was_owed() {
+        let mut bank = test_bank();
+        let mut balance = test_balance(I80F48::from_num(10));
+
+        let repaid = apply_deposit(&mut balance, &mut bank, I80F48::from_num(1_000)).unwrap();
+
+        assert_eq!(repaid, I80F48::from_num(10));
+        assert_eq!(I80F48::from(balance.liability_shares), I80F48::ZERO);
+        // The excess becomes a deposit rather than vanishing or counting as repayment.
+        assert_eq!(I80F48::from(balance.deposit_shares), I80F48::from_num(990));
+    }
+
+    #[test]
+    fn repaying_against_zero_liability_repays_nothing() {
+        let mut bank = test_bank();
+        let mut balance = test_balance(I80F48::ZERO);
+
+        let repaid = apply_deposit(&mut balance, &mut bank, I80F48::from_num(500)).unwrap();
+
+        assert_eq!(repaid, I80F48::ZERO);
+    }
+
+    #[test]
+    fn seized_value_is_zero_when_nothing_was_repaid_regardless_of_bonus() {
+        let seized = calc_seized_value(I80F48::ZERO, I80F48::from_num(0.1)).unwrap();
+        assert_eq!(seized, I80F48::ZERO);
+    }
+
+    #[test]
+    fn seized_value_applies_the_liquidation_bonus_to_the_repaid_amount() {
+        let seized = calc_seized_value(I80F48::from_num(100), I80F48::from_num(0.1)).unwrap();
+        assert_eq!(seized, I80F48::from_num(110));
+    }
+
+    fn test_price(price: i64, conf: u64) -> Price {
+        Price {
+            price,
+            conf,
+            expo: EXPONENT,
+            publish_time: 0,
+        }
+    }
+
+    #[test]
+    fn unbiased_price_ignores_confidence() {
+        let price = pyth_price_to_i80f48(&test_price(1_000, 100), None).unwrap();
+        assert_eq!(price, I80F48::from_num(1_000));
+    }
+
+    #[test]
+    fn low_bias_subtracts_the_scaled_confidence_interval() {
+        let price = pyth_price_to_i80f48(&test_price(1_000, 100), Some(PriceBias::Low)).unwrap();
+        assert_eq!(price, I80F48::from_num(788));
+    }
+
+    #[test]
+    fn high_bias_adds_the_scaled_confidence_interval() {
+        let price = pyth_price_to_i80f48(&test_price(1_000, 100), Some(PriceBias::High)).unwrap();
+        assert_eq!(price, I80F48::from_num(1_212));
+    }
+}
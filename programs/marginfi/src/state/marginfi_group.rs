@@ -0,0 +1,550 @@
+use super::marginfi_account::WeightType;
+use crate::{
+    check,
+    constants::SECONDS_PER_YEAR,
+    prelude::{MarginfiError, MarginfiResult},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Transfer};
+use fixed::types::I80F48;
+use marginfi_macros::cm;
+use pyth_sdk_solana::{load_price_feed_from_account_info, Price, PriceFeed};
+use std::{
+    cmp::{max, min},
+    collections::HashMap,
+};
+
+/// Fixed-point value stored in a zero-copy account. `I80F48` itself is not `Pod`, so it is
+/// round-tripped through its little-endian byte representation at the account boundary.
+#[zero_copy]
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct WrappedI80F48 {
+    pub value: [u8; 16],
+}
+
+impl From<I80F48> for WrappedI80F48 {
+    fn from(value: I80F48) -> Self {
+        Self {
+            value: value.to_le_bytes(),
+        }
+    }
+}
+
+impl From<WrappedI80F48> for I80F48 {
+    fn from(value: WrappedI80F48) -> Self {
+        Self::from_le_bytes(value.value)
+    }
+}
+
+pub const MAX_LENDING_POOL_BANKS: usize = 16;
+
+#[account(zero_copy)]
+pub struct MarginfiGroup {
+    pub admin: Pubkey,
+    pub lending_pool: LendingPool,
+}
+
+#[zero_copy]
+pub struct LendingPool {
+    pub banks: [Option<Bank>; MAX_LENDING_POOL_BANKS],
+}
+
+/// A slow-moving reference price tracked alongside the Pyth spot price, used to dampen the
+/// effect of short-lived oracle spikes on borrow-time health checks.
+#[zero_copy]
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct StablePriceModel {
+    /// The reference price, in the same exponent-6 convention as `pyth_price_to_i80f48`.
+    pub stable_price: WrappedI80F48,
+    pub last_update_ts: i64,
+    /// Maximum distance, in bps of the current stable price, the stable price may move per
+    /// second of elapsed time toward the oracle price.
+    pub max_move_bps_per_second: WrappedI80F48,
+}
+
+impl StablePriceModel {
+    /// Move the stable price toward `oracle_price`, capped at `max_move_bps_per_second * elapsed`.
+    pub fn update(&mut self, oracle_price: I80F48, current_timestamp: i64) -> MarginfiResult {
+        let stable_price: I80F48 = self.stable_price.into();
+
+        if stable_price == I80F48::ZERO {
+            self.stable_price = oracle_price.into();
+            self.last_update_ts = current_timestamp;
+            return Ok(());
+        }
+
+        let elapsed = cm!(current_timestamp - self.last_update_ts);
+        if elapsed <= 0 {
+            return Ok(());
+        }
+
+        let max_move_bps: I80F48 = self.max_move_bps_per_second.into();
+        let max_delta = cm!(stable_price * max_move_bps * I80F48::from_num(elapsed) / I80F48::from_num(10_000));
+
+        let diff = cm!(oracle_price - stable_price);
+        let clamped_diff = max(min(diff, max_delta), -max_delta);
+
+        self.stable_price = cm!(stable_price + clamped_diff).into();
+        self.last_update_ts = current_timestamp;
+
+        Ok(())
+    }
+}
+
+#[zero_copy]
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct BankConfig {
+    pub pyth_oracle: Pubkey,
+
+    pub deposit_weight_init: WrappedI80F48,
+    pub deposit_weight_maint: WrappedI80F48,
+    pub liability_weight_init: WrappedI80F48,
+    pub liability_weight_maint: WrappedI80F48,
+
+    /// Maximum tolerated `conf / price` ratio on the Pyth feed. Prices reporting a wider
+    /// confidence interval than this are rejected outright rather than used.
+    pub max_confidence_ratio: WrappedI80F48,
+    /// Maximum allowed age, in seconds, between the Pyth feed's `publish_time` and the current
+    /// `Clock` before the price is considered stale and rejected.
+    pub max_staleness_seconds: i64,
+
+    /// Extra fraction of the repaid value (e.g. `0.05` = 5%) a liquidator receives in collateral
+    /// when liquidating an unhealthy position holding this bank's asset.
+    pub liquidation_bonus: WrappedI80F48,
+}
+
+impl BankConfig {
+    pub fn get_weights(&self, weight_type: WeightType) -> (I80F48, I80F48) {
+        match weight_type {
+            WeightType::Initial => (
+                self.deposit_weight_init.into(),
+                self.liability_weight_init.into(),
+            ),
+            WeightType::Maintenance => (
+                self.deposit_weight_maint.into(),
+                self.liability_weight_maint.into(),
+            ),
+        }
+    }
+}
+
+#[account(zero_copy)]
+pub struct Bank {
+    pub mint_pk: Pubkey,
+    pub mint_decimals: u8,
+
+    pub vault: Pubkey,
+    pub vault_authority_bump: u8,
+
+    pub total_deposit_shares: WrappedI80F48,
+    pub total_liability_shares: WrappedI80F48,
+
+    /// Cumulative growth factor applied to deposit shares to get the underlying deposit amount.
+    /// Starts at `1` and only ever grows as borrow interest is shared out to depositors.
+    pub deposit_index: WrappedI80F48,
+    /// Cumulative growth factor applied to liability shares to get the underlying liability
+    /// amount. Starts at `1` and only ever grows as borrow interest accrues.
+    pub borrow_index: WrappedI80F48,
+    pub last_update_ts: i64,
+
+    /// Utilization (0..1) of the first kink of the interest rate curve.
+    pub util0: WrappedI80F48,
+    /// Borrow APR at `util0`.
+    pub rate0: WrappedI80F48,
+    /// Utilization (0..1) of the second kink of the interest rate curve.
+    pub util1: WrappedI80F48,
+    /// Borrow APR at `util1`.
+    pub rate1: WrappedI80F48,
+    /// Borrow APR at 100% utilization.
+    pub max_rate: WrappedI80F48,
+
+    pub stable_price_model: StablePriceModel,
+
+    /// Fraction of a new borrow's value minted as extra liability shares against the borrower and
+    /// credited to `collected_fees`, e.g. `0.003` = 30bps origination fee.
+    pub loan_origination_fee_rate: WrappedI80F48,
+    /// Fraction of accrued borrow interest skimmed into `collected_fees` instead of being passed
+    /// through to depositors.
+    pub loan_fee_rate: WrappedI80F48,
+    /// Protocol revenue accumulated from origination and ongoing borrow fees, denominated in the
+    /// bank's underlying asset, withdrawable via `withdraw_fees`.
+    pub collected_fees: WrappedI80F48,
+
+    pub config: BankConfig,
+}
+
+impl Bank {
+    pub fn load_price_feed(
+        &self,
+        pyth_accounts: &HashMap<Pubkey, &AccountInfo>,
+    ) -> MarginfiResult<PriceFeed> {
+        let price_feed_ai = pyth_accounts
+            .get(&self.config.pyth_oracle)
+            .ok_or_else(|| error!(MarginfiError::BankNotFound))?;
+
+        load_price_feed_from_account_info(price_feed_ai).map_err(|_| error!(MarginfiError::BankNotFound))
+    }
+
+    /// Validate a fresh Pyth price against this bank's staleness and confidence-ratio
+    /// configuration, returning the raw `Price` on success. Shared by borrow-time health pricing
+    /// and anything else that needs a trustworthy spot price for this bank's asset.
+    pub fn get_checked_price(&self, price_feed: &PriceFeed, current_timestamp: i64) -> MarginfiResult<Price> {
+        let price = price_feed
+            .get_price_no_older_than(current_timestamp, self.config.max_staleness_seconds as u64)
+            .ok_or_else(|| error!(MarginfiError::StaleOracle))?;
+
+        let confidence_ratio = cm!(I80F48::from_num(price.conf) / I80F48::from_num(price.price));
+        check!(
+            confidence_ratio <= self.config.max_confidence_ratio.into(),
+            MarginfiError::UnreliableOracle
+        );
+
+        Ok(price)
+    }
+
+    /// Nudge the stable price model toward a freshly validated oracle reading for this bank's
+    /// asset. Called from the share-mutating paths (deposit/borrow/withdraw) the same way
+    /// `accrue_interest` is.
+    pub fn update_stable_price_from_feed(&mut self, price_feed: &PriceFeed) -> MarginfiResult {
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let price = self.get_checked_price(price_feed, current_timestamp)?;
+        let scaled_price = crate::state::marginfi_account::pyth_price_to_i80f48(&price, None)?;
+        self.update_stable_price(scaled_price)
+    }
+
+    pub fn get_deposit_amount(&self, shares: I80F48) -> MarginfiResult<I80F48> {
+        let deposit_index: I80F48 = self.deposit_index.into();
+        Ok(cm!(shares * deposit_index))
+    }
+
+    pub fn get_liability_amount(&self, shares: I80F48) -> MarginfiResult<I80F48> {
+        let borrow_index: I80F48 = self.borrow_index.into();
+        Ok(cm!(shares * borrow_index))
+    }
+
+    pub fn get_deposit_shares(&self, amount: I80F48) -> MarginfiResult<I80F48> {
+        let deposit_index: I80F48 = self.deposit_index.into();
+        Ok(cm!(amount / deposit_index))
+    }
+
+    pub fn get_liability_shares(&self, amount: I80F48) -> MarginfiResult<I80F48> {
+        let borrow_index: I80F48 = self.borrow_index.into();
+        Ok(cm!(amount / borrow_index))
+    }
+
+    /// Bring `deposit_index`/`borrow_index` up to date with the current `Clock`, applying the
+    /// interest accrued since `last_update_ts` according to the two-kink utilization curve.
+    /// Depositors earn the borrowers' interest pro-rata with utilization, less the
+    /// `loan_fee_rate` slice credited to `collected_fees`.
+    pub fn accrue_interest(&mut self) -> MarginfiResult {
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        self.accrue_interest_to(current_timestamp)
+    }
+
+    fn accrue_interest_to(&mut self, current_timestamp: i64) -> MarginfiResult {
+        let time_delta = cm!(current_timestamp - self.last_update_ts);
+
+        if time_delta <= 0 {
+            return Ok(());
+        }
+
+        let total_deposits = self.get_deposit_amount(self.total_deposit_shares.into())?;
+        let total_liabilities = self.get_liability_amount(self.total_liability_shares.into())?;
+
+        let borrow_rate = self.get_borrow_rate(total_deposits, total_liabilities)?;
+
+        let dt = I80F48::from_num(time_delta);
+        let year = I80F48::from_num(SECONDS_PER_YEAR);
+        let borrow_interest_factor = cm!(borrow_rate * dt / year);
+
+        let borrow_index: I80F48 = self.borrow_index.into();
+        self.borrow_index = cm!(borrow_index * (I80F48::from_num(1) + borrow_interest_factor)).into();
+
+        if total_deposits > I80F48::ZERO {
+            let utilization = cm!(total_liabilities / total_deposits);
+            let deposit_interest_factor = cm!(borrow_interest_factor * utilization);
+
+            // The protocol skims a slice of the accrued interest before it's shared out to
+            // depositors; the rest compounds into `deposit_index` as before.
+            let loan_fee_rate: I80F48 = self.loan_fee_rate.into();
+            let fee_factor = cm!(deposit_interest_factor * loan_fee_rate);
+            let depositor_factor = cm!(deposit_interest_factor - fee_factor);
+
+            let fee_value = cm!(total_deposits * fee_factor);
+            self.add_collected_fees(fee_value)?;
+
+            let deposit_index: I80F48 = self.deposit_index.into();
+            self.deposit_index = cm!(deposit_index * (I80F48::from_num(1) + depositor_factor)).into();
+        }
+
+        self.last_update_ts = current_timestamp;
+
+        Ok(())
+    }
+
+    /// Mint extra liability shares equal to `liability_value_delta * loan_origination_fee_rate`
+    /// against the borrower, crediting the same value to `collected_fees`. Returns the liability
+    /// share delta the caller still needs to apply to the borrower's own `Balance`.
+    pub fn apply_loan_origination_fee(&mut self, liability_value_delta: I80F48) -> MarginfiResult<I80F48> {
+        let origination_fee_rate: I80F48 = self.loan_origination_fee_rate.into();
+        let fee_value = cm!(liability_value_delta * origination_fee_rate);
+        let fee_shares_delta = self.get_liability_shares(fee_value)?;
+
+        self.change_liability_shares(fee_shares_delta)?;
+        self.add_collected_fees(fee_value)?;
+
+        Ok(fee_shares_delta)
+    }
+
+    pub fn add_collected_fees(&mut self, value: I80F48) -> MarginfiResult {
+        let mut collected_fees: I80F48 = self.collected_fees.into();
+        cm!(collected_fees += value);
+        self.collected_fees = collected_fees.into();
+        Ok(())
+    }
+
+    /// Withdraw `amount` of the accumulated `collected_fees` from the bank's vault through an SPL
+    /// transfer signed with `signer_seeds`, failing with `InsufficientFees` if `amount` exceeds
+    /// them. It does not check that the group admin authorized the withdrawal.
+    pub fn withdraw_fees(
+        &mut self,
+        amount: u64,
+        accounts: Transfer,
+        program: AccountInfo,
+        signer_seeds: &[&[&[u8]]],
+    ) -> MarginfiResult {
+        let mut collected_fees: I80F48 = self.collected_fees.into();
+        check!(
+            I80F48::from_num(amount) <= collected_fees,
+            MarginfiError::InsufficientFees
+        );
+        cm!(collected_fees -= I80F48::from_num(amount));
+        self.collected_fees = collected_fees.into();
+
+        self.withdraw_spl_transfer(amount, accounts, program, signer_seeds)
+    }
+
+    /// Nudge the stable price model toward the latest oracle reading. Called by
+    /// `update_stable_price_from_feed` on deposit, borrow and withdraw; health checks read the
+    /// oracle without updating the stable price.
+    pub fn update_stable_price(&mut self, oracle_price: I80F48) -> MarginfiResult {
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        self.stable_price_model.update(oracle_price, current_timestamp)
+    }
+
+    /// Piecewise-linear borrow APR: `0..rate0` below `util0`, `rate0..rate1` between `util0` and
+    /// `util1`, and `rate1..max_rate` above `util1`.
+    fn get_borrow_rate(
+        &self,
+        total_deposits: I80F48,
+        total_liabilities: I80F48,
+    ) -> MarginfiResult<I80F48> {
+        let utilization = if total_deposits == I80F48::ZERO {
+            I80F48::ZERO
+        } else {
+            cm!(total_liabilities / total_deposits)
+        };
+
+        let util0: I80F48 = self.util0.into();
+        let util1: I80F48 = self.util1.into();
+        let rate0: I80F48 = self.rate0.into();
+        let rate1: I80F48 = self.rate1.into();
+        let max_rate: I80F48 = self.max_rate.into();
+
+        if utilization <= util0 {
+            interpolate(utilization, I80F48::ZERO, util0, I80F48::ZERO, rate0)
+        } else if utilization <= util1 {
+            interpolate(utilization, util0, util1, rate0, rate1)
+        } else {
+            interpolate(
+                utilization.min(I80F48::from_num(1)),
+                util1,
+                I80F48::from_num(1),
+                rate1,
+                max_rate,
+            )
+        }
+    }
+
+    pub fn change_deposit_shares(&mut self, delta: I80F48) -> MarginfiResult {
+        let mut total_deposit_shares: I80F48 = self.total_deposit_shares.into();
+        cm!(total_deposit_shares += delta);
+        self.total_deposit_shares = total_deposit_shares.into();
+        Ok(())
+    }
+
+    pub fn change_liability_shares(&mut self, delta: I80F48) -> MarginfiResult {
+        let mut total_liability_shares: I80F48 = self.total_liability_shares.into();
+        cm!(total_liability_shares += delta);
+        self.total_liability_shares = total_liability_shares.into();
+        Ok(())
+    }
+
+    pub fn deposit_spl_transfer(
+        &self,
+        amount: u64,
+        accounts: Transfer,
+        program: AccountInfo,
+    ) -> MarginfiResult {
+        transfer(CpiContext::new(program, accounts), amount)
+    }
+
+    pub fn withdraw_spl_transfer(
+        &self,
+        amount: u64,
+        accounts: Transfer,
+        program: AccountInfo,
+        signer_seeds: &[&[&[u8]]],
+    ) -> MarginfiResult {
+        transfer(
+            CpiContext::new_with_signer(program, accounts, signer_seeds),
+            amount,
+        )
+    }
+}
+
+/// Linearly interpolate `y` for `x` within `[x0, x1] -> [y0, y1]`.
+fn interpolate(x: I80F48, x0: I80F48, x1: I80F48, y0: I80F48, y1: I80F48) -> MarginfiResult<I80F48> {
+    let slope = cm!((y1 - y0) / (x1 - x0));
+
+    Ok(cm!((x - x0) * slope + y0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_bank() -> Bank {
+        Bank {
+            mint_pk: Pubkey::default(),
+            mint_decimals: 0,
+            vault: Pubkey::default(),
+            vault_authority_bump: 0,
+            total_deposit_shares: I80F48::from_num(1_000).into(),
+            total_liability_shares: I80F48::from_num(500).into(),
+            deposit_index: I80F48::from_num(1).into(),
+            borrow_index: I80F48::from_num(1).into(),
+            last_update_ts: 0,
+            util0: I80F48::from_num(0.5).into(),
+            rate0: I80F48::from_num(0.1).into(),
+            util1: I80F48::from_num(0.8).into(),
+            rate1: I80F48::from_num(0.3).into(),
+            max_rate: I80F48::from_num(1).into(),
+            stable_price_model: StablePriceModel::default(),
+            loan_origination_fee_rate: I80F48::ZERO.into(),
+            loan_fee_rate: I80F48::ZERO.into(),
+            collected_fees: I80F48::ZERO.into(),
+            config: BankConfig::default(),
+        }
+    }
+
+    #[test]
+    fn interest_compounds_across_multiple_accruals() {
+        let mut bank = test_bank();
+
+        bank.accrue_interest_to(SECONDS_PER_YEAR).unwrap();
+        let borrow_index_after_one_year: I80F48 = bank.borrow_index.into();
+        let deposit_index_after_one_year: I80F48 = bank.deposit_index.into();
+
+        // 50% utilization sits exactly at util0, so the borrow APR is rate0 = 10%.
+        assert_eq!(borrow_index_after_one_year, I80F48::from_num(1.1));
+        // Depositors receive the borrowers' interest scaled by utilization (50%).
+        assert_eq!(deposit_index_after_one_year, I80F48::from_num(1.05));
+
+        bank.accrue_interest_to(2 * SECONDS_PER_YEAR).unwrap();
+        let borrow_index_after_two_years: I80F48 = bank.borrow_index.into();
+        assert!(borrow_index_after_two_years > borrow_index_after_one_year);
+    }
+
+    #[test]
+    fn origination_fee_moves_borrower_liability_and_bank_fees_together() {
+        let mut bank = test_bank();
+        bank.loan_origination_fee_rate = I80F48::from_num(0.01).into();
+
+        let total_liability_shares_before: I80F48 = bank.total_liability_shares.into();
+        let collected_fees_before: I80F48 = bank.collected_fees.into();
+
+        let fee_shares_delta = bank.apply_loan_origination_fee(I80F48::from_num(100)).unwrap();
+
+        let total_liability_shares_after: I80F48 = bank.total_liability_shares.into();
+        let collected_fees_after: I80F48 = bank.collected_fees.into();
+
+        assert_eq!(fee_shares_delta, I80F48::from_num(1));
+        assert_eq!(
+            total_liability_shares_after,
+            total_liability_shares_before + fee_shares_delta
+        );
+        assert_eq!(collected_fees_after, collected_fees_before + I80F48::from_num(1));
+    }
+
+    #[test]
+    fn stable_price_adopts_the_oracle_price_on_first_update() {
+        let mut model = StablePriceModel::default();
+
+        model.update(I80F48::from_num(100), 1_000).unwrap();
+
+        assert_eq!(I80F48::from(model.stable_price), I80F48::from_num(100));
+        assert_eq!(model.last_update_ts, 1_000);
+    }
+
+    #[test]
+    fn stable_price_moves_fully_toward_oracle_price_within_the_allowed_cap() {
+        let mut model = StablePriceModel {
+            stable_price: I80F48::from_num(100).into(),
+            last_update_ts: 0,
+            max_move_bps_per_second: I80F48::from_num(100).into(),
+        };
+
+        // 100 bps/second * 10 seconds = 1,000 bps = 10% of 100, i.e. a move of 10 is allowed;
+        // the oracle only moved 5, so the stable price should track it exactly.
+        model.update(I80F48::from_num(105), 10).unwrap();
+
+        assert_eq!(I80F48::from(model.stable_price), I80F48::from_num(105));
+    }
+
+    #[test]
+    fn stable_price_move_is_clamped_when_the_oracle_jumps_too_far() {
+        let mut model = StablePriceModel {
+            stable_price: I80F48::from_num(100).into(),
+            last_update_ts: 0,
+            max_move_bps_per_second: I80F48::from_num(10).into(),
+        };
+
+        // 10 bps/second * 1 second = 10 bps = 0.1% of 100, i.e. a move of 0.1 is allowed, far
+        // short of the oracle's jump to 200.
+        model.update(I80F48::from_num(200), 1).unwrap();
+
+        assert_eq!(I80F48::from(model.stable_price), I80F48::from_num(100.1));
+    }
+
+    #[test]
+    fn stable_price_clamp_also_applies_when_the_oracle_price_drops() {
+        let mut model = StablePriceModel {
+            stable_price: I80F48::from_num(100).into(),
+            last_update_ts: 0,
+            max_move_bps_per_second: I80F48::from_num(10).into(),
+        };
+
+        model.update(I80F48::from_num(50), 1).unwrap();
+
+        // 99.9, computed in I80F48 as 100 minus the 0.1 allowed move.
+        assert_eq!(
+            I80F48::from(model.stable_price),
+            I80F48::from_num(100) - I80F48::from_num(1_000) / I80F48::from_num(10_000)
+        );
+    }
+
+    #[test]
+    fn stable_price_does_not_move_when_no_time_has_elapsed() {
+        let mut model = StablePriceModel {
+            stable_price: I80F48::from_num(100).into(),
+            last_update_ts: 10,
+            max_move_bps_per_second: I80F48::from_num(100).into(),
+        };
+
+        model.update(I80F48::from_num(500), 10).unwrap();
+
+        assert_eq!(I80F48::from(model.stable_price), I80F48::from_num(100));
+        assert_eq!(model.last_update_ts, 10);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod marginfi_account;
+pub mod marginfi_group;